@@ -1,47 +1,539 @@
 use std::{
-    fs,
-    path::{Path, PathBuf},
+    collections::hash_map::DefaultHasher,
+    collections::{HashMap, HashSet},
+    fmt,
+    fs::{self, File},
+    hash::{Hash, Hasher},
+    io::{self, Read},
+    path::{Component, Path, PathBuf},
 };
 
-/// Error when creating runner from args
+/// Error when creating a [`Runner`] from command-line args
 #[derive(Debug, PartialEq)]
 pub enum FromArgsError {
     InsufficientArguments,
-    TooManyArguments,
-    InvalidFilePath,
-    NotADirectory,
+    MissingOptionValue {
+        option: String,
+    },
+    InvalidOptionValue {
+        option: String,
+        value: String,
+    },
+    InvalidPath {
+        path: PathBuf,
+        kind: io::ErrorKind,
+    },
+    NotADirectory {
+        path: PathBuf,
+    },
+}
+
+impl fmt::Display for FromArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FromArgsError::InsufficientArguments => {
+                write!(f, "expected at least one root directory to scan")
+            }
+            FromArgsError::MissingOptionValue { option } => {
+                write!(f, "missing value for {option}")
+            }
+            FromArgsError::InvalidOptionValue { option, value } => {
+                write!(f, "invalid value {value:?} for {option}")
+            }
+            FromArgsError::InvalidPath { path, kind } => {
+                write!(f, "cannot access {}: {}", path.display(), kind)
+            }
+            FromArgsError::NotADirectory { path } => {
+                write!(f, "not a directory: {}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for FromArgsError {}
+
+/// Error while running a scan, unifying argument parsing failures and
+/// per-run I/O failures so callers can propagate a single error type.
+#[derive(Debug)]
+pub enum RunError {
+    Args(FromArgsError),
+    Io(io::Error),
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunError::Args(err) => write!(f, "{err}"),
+            RunError::Io(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for RunError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RunError::Args(err) => Some(err),
+            RunError::Io(err) => Some(err),
+        }
+    }
+}
+
+impl From<FromArgsError> for RunError {
+    fn from(err: FromArgsError) -> Self {
+        RunError::Args(err)
+    }
+}
+
+impl From<io::Error> for RunError {
+    fn from(err: io::Error) -> Self {
+        RunError::Io(err)
+    }
+}
+
+/// Number of leading bytes read for the cheap "partial" hash pass
+const PARTIAL_HASH_SIZE: usize = 4096;
+/// Chunk size used when streaming a file for the full hash pass
+const FULL_HASH_CHUNK_SIZE: usize = 64 * 1024;
+/// How many directories deep the walk will descend before giving up on a
+/// branch, as a backstop against pathologically deep or cyclic trees
+const MAX_WALK_DEPTH: usize = 128;
+
+/// Outcome of a scan: the duplicate groups that were found, plus any
+/// individual entries that couldn't be read along the way.
+#[derive(Debug)]
+pub struct ScanReport {
+    pub duplicates: Vec<Vec<PathBuf>>,
+    pub warnings: Vec<(PathBuf, io::Error)>,
+}
+
+/// How a walked filesystem entry was classified, so the walker can treat
+/// each kind deliberately instead of lumping everything into "file or dir".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryType {
+    Regular,
+    Directory,
+    Symlink,
+    /// Anything that is not a regular file, directory or symlink (e.g. a
+    /// FIFO, socket or device file); the walker skips these
+    Absent,
+}
+
+fn classify_entry(file_type: &fs::FileType) -> EntryType {
+    if file_type.is_symlink() {
+        EntryType::Symlink
+    } else if file_type.is_dir() {
+        EntryType::Directory
+    } else if file_type.is_file() {
+        EntryType::Regular
+    } else {
+        EntryType::Absent
+    }
+}
+
+/// Identifies a file's physical inode on Unix, so hardlinked paths pointing
+/// at the same data can be recognised and collapsed rather than reported as
+/// content duplicates.
+#[cfg(unix)]
+fn file_identity(meta: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_identity(_meta: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Options controlling how a scan is carried out, as opposed to *where*
+/// (that's the root directories on [`Runner`]).
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ScanOptions {
+    /// Files smaller than this are ignored entirely
+    pub min_size: u64,
+    /// Follow symlinked files/directories during the walk
+    pub follow_symlinks: bool,
+    /// Ignore zero-length files, which would otherwise all match each other
+    pub ignore_empty: bool,
 }
 
 #[derive(Debug, PartialEq)]
 pub struct Runner {
-    /// Path is relative to the root of the project
-    root_dir: PathBuf,
+    /// Absolute, lexically-normalized root directories, without duplicates
+    root_dirs: Vec<PathBuf>,
+    options: ScanOptions,
 }
 
 impl Runner {
     /// NOTE: first arg is path to binary (always included)
-    /// second arg should be path to root directory to check
+    /// remaining args are one or more root directories to scan, plus any of
+    /// `--min-size <bytes>`, `--follow-symlinks`, `--ignore-empty`
     pub fn from_args(args: Vec<String>) -> Result<Runner, FromArgsError> {
-        // check args count
-        let args_len = args.len();
-        if args_len < 2 {
+        if args.len() < 2 {
             return Err(FromArgsError::InsufficientArguments);
         }
-        if args_len > 2 {
-            return Err(FromArgsError::TooManyArguments);
+
+        let mut root_dirs = Vec::new();
+        let mut options = ScanOptions::default();
+
+        let mut rest = args.into_iter().skip(1);
+        while let Some(arg) = rest.next() {
+            match arg.as_str() {
+                "--follow-symlinks" => options.follow_symlinks = true,
+                "--ignore-empty" => options.ignore_empty = true,
+                "--min-size" => {
+                    let value = rest.next().ok_or_else(|| FromArgsError::MissingOptionValue {
+                        option: arg.clone(),
+                    })?;
+                    options.min_size =
+                        value
+                            .parse()
+                            .map_err(|_| FromArgsError::InvalidOptionValue {
+                                option: arg.clone(),
+                                value,
+                            })?;
+                }
+                _ => root_dirs.push(Path::new(&arg).to_owned()),
+            }
         }
 
-        let root_dir = Path::new(&args[1]).to_owned();
+        if root_dirs.is_empty() {
+            return Err(FromArgsError::InsufficientArguments);
+        }
 
-        // ensure path is to dir that exists
-        match root_dir.metadata() {
-            Err(_) => Err(FromArgsError::InvalidFilePath),
-            Ok(r) => {
-                if r.is_dir() {
-                    return Ok(Runner { root_dir });
+        // ensure every path is to a dir that exists
+        for root_dir in &root_dirs {
+            match root_dir.metadata() {
+                Err(err) => {
+                    return Err(FromArgsError::InvalidPath {
+                        path: root_dir.clone(),
+                        kind: err.kind(),
+                    })
                 }
-                Err(FromArgsError::NotADirectory)
+                Ok(meta) if !meta.is_dir() => {
+                    return Err(FromArgsError::NotADirectory {
+                        path: root_dir.clone(),
+                    })
+                }
+                Ok(_) => {}
+            }
+        }
+
+        // normalize so roots given as relative paths, or with redundant `.`/`..`
+        // components, compare and report consistently, then dedup so the same
+        // directory listed twice isn't walked (and its files reported as
+        // duplicates of themselves)
+        let mut seen_roots = HashSet::new();
+        let root_dirs = root_dirs
+            .into_iter()
+            .map(|dir| normalize_lexically(&dir))
+            .filter(|dir| seen_roots.insert(dir.clone()))
+            .collect();
+
+        Ok(Runner {
+            root_dirs,
+            options,
+        })
+    }
+
+    /// Finds groups of byte-identical files under the root directories.
+    ///
+    /// Uses a staged pipeline to avoid hashing every file in full: files are
+    /// first bucketed by size, then by a cheap partial hash of their leading
+    /// bytes, and only the survivors of both stages are fully hashed. Each
+    /// stage drops buckets with a single entry, since those can't be
+    /// duplicates. Directory entries that fail to be listed or inspected
+    /// during the walk are skipped and reported as warnings; files that fail
+    /// to be read while being sized or hashed are skipped without a warning.
+    /// Neither aborts the whole run.
+    pub fn find_duplicates(&self) -> Result<ScanReport, RunError> {
+        let mut warnings = Vec::new();
+        let mut files = Vec::new();
+        // shared across every root so a file reachable from two roots (e.g.
+        // overlapping or hardlinked trees) is only collected once
+        let mut visited_dirs = HashSet::new();
+        let mut seen_inodes = HashSet::new();
+        for root_dir in &self.root_dirs {
+            files.extend(walk_files(
+                root_dir,
+                self.options.follow_symlinks,
+                &mut visited_dirs,
+                &mut seen_inodes,
+                &mut warnings,
+            )?);
+        }
+
+        let options = &self.options;
+        let by_size = bucket_by(files, |path| {
+            let len = path.metadata().ok()?.len();
+            if options.ignore_empty && len == 0 {
+                return None;
             }
+            if len < options.min_size {
+                return None;
+            }
+            Some(len)
+        });
+        let candidates: Vec<PathBuf> = by_size
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .flatten()
+            .collect();
+
+        let by_partial_hash = bucket_by(candidates, |path| hash_partial(path).ok());
+        let candidates: Vec<PathBuf> = by_partial_hash
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .flatten()
+            .collect();
+
+        let by_full_hash = bucket_by(candidates, |path| hash_full(path).ok());
+
+        let duplicates = by_full_hash
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .map(|group| group.into_iter().map(canonicalize_or_original).collect())
+            .collect();
+
+        Ok(ScanReport {
+            duplicates,
+            warnings,
+        })
+    }
+}
+
+/// Resolves `path` to an absolute, lexically-normalized form: relative paths
+/// are joined onto the current directory, `.` components are dropped, and
+/// `..` components are collapsed against the preceding component where
+/// possible. This doesn't touch the filesystem, so it works even for paths
+/// that don't exist (yet), unlike [`Path::canonicalize`].
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let absolute = if path.is_absolute() {
+        path.to_owned()
+    } else {
+        std::env::current_dir()
+            .map(|cwd| cwd.join(path))
+            .unwrap_or_else(|_| path.to_owned())
+    };
+
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !matches!(normalized.components().next_back(), Some(Component::RootDir) | None)
+                {
+                    normalized.pop();
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Canonicalizes `path` (resolving symlinks so the same file always reports
+/// under the same name), falling back to the original path if that fails.
+fn canonicalize_or_original(path: PathBuf) -> PathBuf {
+    fs::canonicalize(&path).unwrap_or(path)
+}
+
+/// Groups `items` by a key computed from `key_fn`, silently dropping any item
+/// for which `key_fn` returns `None`.
+fn bucket_by<T, K, F>(items: Vec<T>, key_fn: F) -> HashMap<K, Vec<T>>
+where
+    K: Eq + std::hash::Hash,
+    F: Fn(&T) -> Option<K>,
+{
+    let mut buckets: HashMap<K, Vec<T>> = HashMap::new();
+    for item in items {
+        if let Some(key) = key_fn(&item) {
+            buckets.entry(key).or_default().push(item);
+        }
+    }
+    buckets
+}
+
+/// Recursively lists every regular file under `dir`.
+///
+/// Entries that can't be read (permission errors, files removed mid-scan,
+/// broken symlinks) are recorded in `warnings` instead of aborting the walk,
+/// so duplicates are still found in the directories that are readable. A
+/// depth guard combined with `visited_dirs` protects against symlink cycles.
+/// Symlinks are only followed when `follow_symlinks` is set, and a symlink is
+/// never reported as a duplicate of its target. Hardlinks to the same
+/// physical file (same device/inode) are collapsed so the same data isn't
+/// reported twice; `visited_dirs` and `seen_inodes` are shared across
+/// multiple calls (one per scan root) so the same directory or file reached
+/// via two roots is still only collected once.
+fn walk_files(
+    dir: &Path,
+    follow_symlinks: bool,
+    visited_dirs: &mut HashSet<PathBuf>,
+    seen_inodes: &mut HashSet<(u64, u64)>,
+    warnings: &mut Vec<(PathBuf, io::Error)>,
+) -> Result<Vec<PathBuf>, RunError> {
+    let mut files = Vec::new();
+    walk_dir(
+        dir,
+        0,
+        follow_symlinks,
+        visited_dirs,
+        seen_inodes,
+        &mut files,
+        warnings,
+    )?;
+    Ok(files)
+}
+
+fn walk_dir(
+    dir: &Path,
+    depth: usize,
+    follow_symlinks: bool,
+    visited_dirs: &mut HashSet<PathBuf>,
+    seen_inodes: &mut HashSet<(u64, u64)>,
+    files: &mut Vec<PathBuf>,
+    warnings: &mut Vec<(PathBuf, io::Error)>,
+) -> Result<(), RunError> {
+    if depth > MAX_WALK_DEPTH {
+        warnings.push((
+            dir.to_owned(),
+            io::Error::other("max walk depth exceeded"),
+        ));
+        return Ok(());
+    }
+
+    if let Ok(canonical) = dir.canonicalize() {
+        if !visited_dirs.insert(canonical) {
+            // already descended into this directory via another path (e.g. a symlink cycle)
+            return Ok(());
+        }
+    }
+
+    let entries = if depth == 0 {
+        fs::read_dir(dir)?
+    } else {
+        match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                warnings.push((dir.to_owned(), err));
+                return Ok(());
+            }
+        }
+    };
+
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                warnings.push((dir.to_owned(), err));
+                continue;
+            }
+        };
+
+        let path = entry.path();
+        let file_type = match entry.file_type() {
+            Ok(file_type) => file_type,
+            Err(err) => {
+                warnings.push((path, err));
+                continue;
+            }
+        };
+
+        match classify_entry(&file_type) {
+            EntryType::Directory => {
+                walk_dir(
+                    &path,
+                    depth + 1,
+                    follow_symlinks,
+                    visited_dirs,
+                    seen_inodes,
+                    files,
+                    warnings,
+                )?;
+            }
+            EntryType::Regular => push_file(path, seen_inodes, files, warnings),
+            EntryType::Symlink => {
+                if !follow_symlinks {
+                    continue;
+                }
+                // resolve what the symlink points at; never treat the link
+                // itself as a duplicate of its target
+                match fs::metadata(&path) {
+                    Ok(meta) if meta.is_dir() => {
+                        walk_dir(
+                            &path,
+                            depth + 1,
+                            follow_symlinks,
+                            visited_dirs,
+                            seen_inodes,
+                            files,
+                            warnings,
+                        )?;
+                    }
+                    Ok(meta) if meta.is_file() => push_file(path, seen_inodes, files, warnings),
+                    Ok(_) => {}
+                    Err(err) => warnings.push((path, err)),
+                }
+            }
+            EntryType::Absent => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Adds `path` to `files`, unless it's a hardlink to a physical file already
+/// collected under another name, in which case it's skipped.
+fn push_file(
+    path: PathBuf,
+    seen_inodes: &mut HashSet<(u64, u64)>,
+    files: &mut Vec<PathBuf>,
+    warnings: &mut Vec<(PathBuf, io::Error)>,
+) {
+    match fs::metadata(&path) {
+        Ok(meta) => {
+            if let Some(identity) = file_identity(&meta) {
+                if !seen_inodes.insert(identity) {
+                    return;
+                }
+            }
+            files.push(path);
+        }
+        Err(err) => warnings.push((path, err)),
+    }
+}
+
+/// Hashes the first [`PARTIAL_HASH_SIZE`] bytes of `path`.
+fn hash_partial(path: &Path) -> io::Result<u64> {
+    let mut file = File::open(path)?;
+    let mut buf = vec![0u8; PARTIAL_HASH_SIZE];
+    // `Read::read` may return short of EOF (e.g. interrupted by a signal), so
+    // keep filling the buffer rather than trusting a single call; otherwise
+    // identical files could hash different leading slices
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    let mut hasher = DefaultHasher::new();
+    buf[..filled].hash(&mut hasher);
+    Ok(hasher.finish())
+}
+
+/// Hashes the full contents of `path`, streaming it in fixed-size chunks so
+/// large files don't need to be loaded into memory at once.
+fn hash_full(path: &Path) -> io::Result<u64> {
+    let mut file = File::open(path)?;
+    let mut hasher = DefaultHasher::new();
+    let mut buf = vec![0u8; FULL_HASH_CHUNK_SIZE];
+    loop {
+        let read = file.read(&mut buf)?;
+        if read == 0 {
+            break;
         }
+        buf[..read].hash(&mut hasher);
     }
+    Ok(hasher.finish())
 }
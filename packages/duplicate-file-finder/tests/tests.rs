@@ -1,3 +1,5 @@
+use std::{fs, io, path::PathBuf};
+
 use duplicate_file_finder::{FromArgsError, Runner};
 
 const BINARY_PATH: &str = "binary/path";
@@ -20,7 +22,10 @@ mod from_args {
     fn error_if_path_does_not_exist() {
         assert_eq!(
             Runner::from_args(vec![BINARY_PATH.to_owned(), String::from("unknown")]),
-            Err(FromArgsError::InvalidFilePath)
+            Err(FromArgsError::InvalidPath {
+                path: PathBuf::from("unknown"),
+                kind: io::ErrorKind::NotFound,
+            })
         )
     }
 
@@ -28,7 +33,9 @@ mod from_args {
     fn error_if_path_is_not_a_dir() {
         assert_eq!(
             Runner::from_args(vec![BINARY_PATH.to_owned(), TEST_FILE_PATH.to_owned()]),
-            Err(FromArgsError::NotADirectory)
+            Err(FromArgsError::NotADirectory {
+                path: PathBuf::from(TEST_FILE_PATH),
+            })
         )
     }
 
@@ -43,14 +50,172 @@ mod from_args {
     }
 
     #[test]
-    fn error_if_extra_args_provided() {
+    fn error_if_one_of_several_paths_is_not_a_dir() {
         assert_eq!(
             Runner::from_args(vec![
                 BINARY_PATH.to_owned(),
                 WITH_NO_DUPLICATES_DIR_PATH.to_owned(),
                 TEST_FILE_PATH.to_owned(),
             ]),
-            Err(FromArgsError::TooManyArguments)
+            Err(FromArgsError::NotADirectory {
+                path: PathBuf::from(TEST_FILE_PATH),
+            })
         )
     }
+
+    #[test]
+    fn ok_if_multiple_dirs_provided() {
+        let result = Runner::from_args(vec![
+            BINARY_PATH.to_owned(),
+            WITH_NO_DUPLICATES_DIR_PATH.to_owned(),
+            WITH_NO_DUPLICATES_DIR_PATH.to_owned(),
+        ]);
+
+        assert!(result.is_ok())
+    }
+
+    #[test]
+    fn ok_with_option_flags() {
+        let result = Runner::from_args(vec![
+            BINARY_PATH.to_owned(),
+            WITH_NO_DUPLICATES_DIR_PATH.to_owned(),
+            String::from("--follow-symlinks"),
+            String::from("--ignore-empty"),
+            String::from("--min-size"),
+            String::from("1024"),
+        ]);
+
+        assert!(result.is_ok())
+    }
+
+    #[test]
+    fn error_if_min_size_value_missing() {
+        assert_eq!(
+            Runner::from_args(vec![
+                BINARY_PATH.to_owned(),
+                WITH_NO_DUPLICATES_DIR_PATH.to_owned(),
+                String::from("--min-size"),
+            ]),
+            Err(FromArgsError::MissingOptionValue {
+                option: String::from("--min-size"),
+            })
+        )
+    }
+
+    #[test]
+    fn error_if_min_size_value_not_a_number() {
+        assert_eq!(
+            Runner::from_args(vec![
+                BINARY_PATH.to_owned(),
+                WITH_NO_DUPLICATES_DIR_PATH.to_owned(),
+                String::from("--min-size"),
+                String::from("not-a-number"),
+            ]),
+            Err(FromArgsError::InvalidOptionValue {
+                option: String::from("--min-size"),
+                value: String::from("not-a-number"),
+            })
+        )
+    }
+}
+
+#[cfg(test)]
+mod find_duplicates {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// Creates a fresh, empty directory under the system temp dir, unique to
+    /// this test run, so tests don't interfere with each other or leave
+    /// behind fixtures that need checking in.
+    fn temp_dir(name: &str) -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!(
+            "duplicate-file-finder-test-{}-{name}-{n}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("failed to create temp dir");
+        dir
+    }
+
+    fn scan(dir: &PathBuf, extra_args: &[&str]) -> duplicate_file_finder::ScanReport {
+        let mut args = vec![BINARY_PATH.to_owned(), dir.to_string_lossy().into_owned()];
+        args.extend(extra_args.iter().map(|arg| arg.to_string()));
+        Runner::from_args(args)
+            .expect("from_args should succeed")
+            .find_duplicates()
+            .expect("find_duplicates should succeed")
+    }
+
+    #[test]
+    fn groups_byte_identical_files() {
+        let dir = temp_dir("identical");
+        fs::write(dir.join("a.txt"), "the same content").unwrap();
+        fs::write(dir.join("b.txt"), "the same content").unwrap();
+
+        let report = scan(&dir, &[]);
+
+        assert_eq!(report.duplicates.len(), 1);
+        let mut group: Vec<String> = report.duplicates[0]
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        group.sort();
+        assert_eq!(group, vec!["a.txt", "b.txt"]);
+    }
+
+    #[test]
+    fn does_not_group_distinct_files() {
+        let dir = temp_dir("distinct");
+        fs::write(dir.join("a.txt"), "content one").unwrap();
+        fs::write(dir.join("b.txt"), "content two, and different length").unwrap();
+
+        let report = scan(&dir, &[]);
+
+        assert!(report.duplicates.is_empty());
+    }
+
+    #[test]
+    fn zero_length_files_form_one_group() {
+        let dir = temp_dir("empty");
+        fs::write(dir.join("a.txt"), "").unwrap();
+        fs::write(dir.join("b.txt"), "").unwrap();
+
+        let report = scan(&dir, &[]);
+
+        assert_eq!(report.duplicates.len(), 1);
+        assert_eq!(report.duplicates[0].len(), 2);
+    }
+
+    #[test]
+    fn ignore_empty_option_drops_zero_length_files() {
+        let dir = temp_dir("empty-ignored");
+        fs::write(dir.join("a.txt"), "").unwrap();
+        fs::write(dir.join("b.txt"), "").unwrap();
+
+        let report = scan(&dir, &["--ignore-empty"]);
+
+        assert!(report.duplicates.is_empty());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn unreadable_entry_is_skipped_into_warnings() {
+        use std::os::unix::fs::symlink;
+
+        let dir = temp_dir("unreadable");
+        // a symlink whose target doesn't exist fails to resolve when followed,
+        // but shouldn't abort the rest of the scan
+        symlink(dir.join("missing-target"), dir.join("broken-link")).unwrap();
+        fs::write(dir.join("a.txt"), "readable content").unwrap();
+        fs::write(dir.join("b.txt"), "readable content").unwrap();
+
+        let report = scan(&dir, &["--follow-symlinks"]);
+
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(report.warnings[0].0, dir.join("broken-link"));
+        assert_eq!(report.duplicates.len(), 1);
+    }
 }